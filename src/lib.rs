@@ -1,8 +1,42 @@
+#[cfg(not(feature = "async"))]
+use std::cell::RefCell;
+use std::io::{Read, Write};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
 #[cfg(feature = "async")]
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+#[cfg(feature = "async")]
+use tokio::sync::Mutex as AsyncMutex;
 
 const PACKET_SIZE: usize = 128;
+const PACKET_SIZE_1K: usize = 1024;
+/// Padding byte used to fill the tail of a short block (SUB, per the YMODEM spec).
+const PADDING_BYTE: u8 = 0x1A;
+/// How often a blocking wait for the peer's response re-checks the
+/// [`CancelToken`], both for the sync poll loop in [`read_byte_cancellable`] (which otherwise
+/// relies on the transport's own read timeout, same as [`poll_byte`]) and for
+/// the bounded `tokio::time::timeout` wrapped around the async read.
+const CANCEL_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(200);
 mod rcv;
+mod receiver;
+pub use receiver::{YmodemCodec, YmodemFrame, YmodemReceiver};
+
+/// Size of the data region carried by each block, selectable on a [`YmodemSender`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockSize {
+    /// 128-byte blocks framed with `Soh` (the original YMODEM block size).
+    Standard,
+    /// 1024-byte blocks framed with `Stx` (YMODEM-1k), roughly halving round-trips.
+    OneK,
+}
+impl BlockSize {
+    fn len(self) -> usize {
+        match self {
+            Self::Standard => PACKET_SIZE,
+            Self::OneK => PACKET_SIZE_1K,
+        }
+    }
+}
 #[derive(Debug)]
 enum YmodemControlCode {
     Soh = 0x01,
@@ -12,13 +46,81 @@ enum YmodemControlCode {
     Nak = 0x15,
     Can = 0x18,
     C = 0x43,
+    G = 0x47,
 }
-#[derive(std::cmp::PartialEq, Debug)]
+/// Which handshake byte the receiver opened the session with, deciding
+/// whether data blocks are individually ACKed or streamed back-to-back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum HandshakeMode {
+    /// Standard YMODEM: the receiver sent `C` and every block is ACKed.
+    Crc,
+    /// YMODEM-G: the receiver sent `G`; data blocks stream unacknowledged
+    /// and only a `Can` from the receiver aborts the transfer early.
+    Streaming,
+}
+impl HandshakeMode {
+    fn control_byte(self) -> u8 {
+        match self {
+            Self::Crc => YmodemControlCode::C as u8,
+            Self::Streaming => YmodemControlCode::G as u8,
+        }
+    }
+}
+/// A cooperative cancellation flag a caller can set from another thread or
+/// task to abort an in-progress transfer.
+///
+/// Checked between packets, and also polled at [`CANCEL_POLL_INTERVAL`] while
+/// blocked waiting for the peer's response, so a stalled or vanished peer can
+/// still be cancelled out of instead of hanging the sender forever. Once set,
+/// the sender emits the standard two-`Can` + backspace abort sequence and
+/// returns [`YmodemError::Cancelled`].
+///
+/// On a sync transport this polling relies on `read` returning (even as a
+/// timeout error) roughly every [`CANCEL_POLL_INTERVAL`]; a transport with no
+/// read timeout configured at all will still block the sender indefinitely,
+/// the same caveat [`poll_byte`] already documents for YMODEM-G streaming.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+impl CancelToken {
+    pub fn new() -> Self {
+        Self(Arc::new(AtomicBool::new(false)))
+    }
+    /// Requests cancellation; takes effect the next time the sender checks.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+    fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+#[derive(Debug)]
 pub enum YmodemError {
     InvalidResponse,
     Timeout,
     RequestReSend,
     SendFailed,
+    /// The transfer was aborted, either by the caller's [`CancelToken`] or by
+    /// a `Can` byte received from the peer.
+    Cancelled,
+    Io(std::io::Error),
+}
+impl PartialEq for YmodemError {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::InvalidResponse, Self::InvalidResponse) => true,
+            (Self::Timeout, Self::Timeout) => true,
+            (Self::RequestReSend, Self::RequestReSend) => true,
+            (Self::SendFailed, Self::SendFailed) => true,
+            (Self::Cancelled, Self::Cancelled) => true,
+            (Self::Io(a), Self::Io(b)) => a.kind() == b.kind(),
+            _ => false,
+        }
+    }
+}
+impl From<std::io::Error> for YmodemError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
 }
 impl std::fmt::Display for YmodemError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -27,35 +129,144 @@ impl std::fmt::Display for YmodemError {
             Self::Timeout => write!(f, "Timeout"),
             Self::RequestReSend => write!(f, "Request re-send"),
             Self::SendFailed => write!(f, "Send failed"),
+            Self::Cancelled => write!(f, "Transfer cancelled"),
+            Self::Io(e) => write!(f, "I/O error: {e}"),
         }
     }
 }
-pub struct YmodemSender<'a> {
-    fname: String,
-    fdata: &'a [u8],
-}
+/// Async counterpart of [`YmodemSend`]: runs over any transport that
+/// implements `AsyncRead + AsyncWrite + Unpin + Send`, while [`YmodemSend`]
+/// takes any `Read + Write` transport (a serial port, a TCP/Unix socket, an
+/// in-memory pipe). `serial2::SerialPort` already satisfies the sync bound,
+/// so no special-casing is needed for the serial use case this crate started from.
 #[cfg(feature = "async")]
 pub trait YmodemAsyncSend {
-    fn send(&self, port: &mut serial2_tokio::SerialPort) -> impl std::future::Future<Output = Result<(), YmodemError>> + Send;
+    fn send<T: AsyncRead + AsyncWrite + Unpin + Send>(
+        &self,
+        port: &mut T,
+    ) -> impl std::future::Future<Output = Result<(), YmodemError>> + Send;
 }
 pub trait YmodemSend {
-    fn send(&self, port: &mut serial2::SerialPort) -> Result<(), YmodemError>;
+    fn send<T: Read + Write>(&self, port: &mut T) -> Result<(), YmodemError>;
 }
 
-impl<'a> YmodemSender<'a> {
-    pub fn new(fname: &str, fdata: &'a [u8]) -> Self {
+/// Total byte length of a [`SenderCore`]'s data source, split out as its own
+/// trait (rather than folded into [`BlockSource`]/`AsyncBlockSource`) so
+/// `create_file_header` can be written once and called from either the sync
+/// or async `send_one` without pulling in a read capability it doesn't need.
+trait SourceLen {
+    fn total_len(&self) -> usize;
+}
+/// An in-memory or incrementally-read source of the block-sized chunks a
+/// YMODEM transfer sends. Implemented by [`SliceSource`] (backing
+/// [`YmodemSender`]) and [`ReaderSource`] (backing [`YmodemStreamSender`]),
+/// so [`SenderCore`] can write the handshake/ACK/EOT logic once for both
+/// instead of duplicating it per source kind.
+trait BlockSource: SourceLen {
+    /// Reads exactly `want` bytes starting at `offset` into the source.
+    fn read_chunk(&self, offset: usize, want: usize) -> Result<Vec<u8>, YmodemError>;
+}
+/// Async counterpart of [`BlockSource`].
+#[cfg(feature = "async")]
+trait AsyncBlockSource: SourceLen {
+    fn read_chunk_async(
+        &self,
+        offset: usize,
+        want: usize,
+    ) -> impl std::future::Future<Output = Result<Vec<u8>, YmodemError>> + Send;
+}
+/// A [`BlockSource`] backing an in-memory payload, used by [`YmodemSender`].
+struct SliceSource<'a>(&'a [u8]);
+impl<'a> SourceLen for SliceSource<'a> {
+    fn total_len(&self) -> usize {
+        self.0.len()
+    }
+}
+impl<'a> BlockSource for SliceSource<'a> {
+    fn read_chunk(&self, offset: usize, want: usize) -> Result<Vec<u8>, YmodemError> {
+        Ok(self.0[offset..offset + want].to_vec())
+    }
+}
+#[cfg(feature = "async")]
+impl<'a> AsyncBlockSource for SliceSource<'a> {
+    async fn read_chunk_async(&self, offset: usize, want: usize) -> Result<Vec<u8>, YmodemError> {
+        self.read_chunk(offset, want)
+    }
+}
+/// A [`BlockSource`] that reads incrementally from `R`, used by
+/// [`YmodemStreamSender`]. `len` is declared up front since it cannot be
+/// discovered by draining `reader` first.
+///
+/// The reader is behind `RefCell` when the `async` feature is off and
+/// `tokio::sync::Mutex` when it's on: `AsyncBlockSource::read_chunk_async`
+/// takes `&self` and is awaited from [`SenderCore`]'s `&self` methods, so the
+/// future must stay `Send` across that await, which requires `Self: Sync` —
+/// `RefCell` can never be `Sync`, but an uncontended `tokio::sync::Mutex` is,
+/// at the cost of a blocking lock in the sync `read_chunk` path below.
+struct ReaderSource<R> {
+    #[cfg(not(feature = "async"))]
+    reader: RefCell<R>,
+    #[cfg(feature = "async")]
+    reader: AsyncMutex<R>,
+    len: usize,
+}
+impl<R> SourceLen for ReaderSource<R> {
+    fn total_len(&self) -> usize {
+        self.len
+    }
+}
+impl<R: Read> BlockSource for ReaderSource<R> {
+    fn read_chunk(&self, _offset: usize, want: usize) -> Result<Vec<u8>, YmodemError> {
+        let mut chunk = vec![0u8; want];
+        #[cfg(not(feature = "async"))]
+        self.reader.borrow_mut().read_exact(&mut chunk)?;
+        #[cfg(feature = "async")]
+        self.reader.blocking_lock().read_exact(&mut chunk)?;
+        Ok(chunk)
+    }
+}
+#[cfg(feature = "async")]
+impl<R: AsyncRead + Unpin + Send> AsyncBlockSource for ReaderSource<R> {
+    async fn read_chunk_async(&self, _offset: usize, want: usize) -> Result<Vec<u8>, YmodemError> {
+        let mut chunk = vec![0u8; want];
+        self.reader.lock().await.read_exact(&mut chunk).await?;
+        Ok(chunk)
+    }
+}
+/// Shared state and wire logic behind [`YmodemSender`] and
+/// [`YmodemStreamSender`]: builds the file-info header, walks `source` one
+/// block at a time, and drives the header/data/`Eot` exchange for a single
+/// file. Generic over [`BlockSource`] so the two public sender types differ
+/// only in how they supply their bytes, not in how those bytes are sent.
+struct SenderCore<S> {
+    fname: String,
+    source: S,
+    /// An `AtomicUsize` rather than a `Cell` so `SenderCore` stays `Sync`,
+    /// which the `async`-feature methods below need (see [`ReaderSource`]'s
+    /// doc comment for why `&self` futures require that).
+    pos: AtomicUsize,
+    block_size: BlockSize,
+    cancel: CancelToken,
+}
+impl<S> SenderCore<S> {
+    fn new(fname: &str, source: S) -> Self {
         Self {
             fname: fname.to_string(),
-            fdata,
+            source,
+            pos: AtomicUsize::new(0),
+            block_size: BlockSize::Standard,
+            cancel: CancelToken::new(),
         }
     }
+}
+impl<S: SourceLen> SenderCore<S> {
     fn create_file_header(&self) -> Vec<u8> {
         let mut header = vec![YmodemControlCode::Soh as u8, 0, 255];
         let mut file_info = Vec::new();
         file_info.extend_from_slice(self.fname.as_bytes());
         file_info.push(0); // null terminator
 
-        file_info.extend_from_slice(self.fdata.len().to_string().as_bytes());
+        file_info.extend_from_slice(self.source.total_len().to_string().as_bytes());
         file_info.push(0x20); // null terminator
         let mut block = [0u8; PACKET_SIZE];
         block[..file_info.len()].copy_from_slice(&file_info);
@@ -65,113 +276,498 @@ impl<'a> YmodemSender<'a> {
         header.push((crc_value & 0xFF) as u8);
         header
     }
-    fn create_data_block(chunk: &[u8], block_number: u8) -> Vec<u8> {
-        let mut block = vec![
-            YmodemControlCode::Soh as u8, /*STX*/
-            block_number,
-            !block_number,
-        ];
-        let mut data = [0u8; PACKET_SIZE];
-        data[..chunk.len()].copy_from_slice(chunk);
-        block.extend_from_slice(&data);
-        // Convert CRC value to little-endian
-        let crc_value = crc16_ccitt(&data);
-        block.push((crc_value >> 8) as u8);
-        block.push((crc_value & 0xFF) as u8);
-        block
-    }
-    fn send_packet(
-        &self,
-        port: &mut serial2::SerialPort,
-        packet: &[u8],
-    ) -> Result<(), YmodemError> {
-        port.write_all(packet).unwrap();
-        while let Err(e) = rcv::wait_for_ack(&mut *port) {
-            if e == YmodemError::RequestReSend {
-                port.write_all(packet).unwrap();
-            } else {
-                return Err(e);
-            }
+}
+impl<S: BlockSource> SenderCore<S> {
+    /// Pulls the next block-sized chunk out of `source`, pairing it with the
+    /// block size its framing should use; `None` once `source` is exhausted.
+    /// A short final read is left for `create_data_block` to SUB-pad.
+    fn next_block(&self) -> Result<Option<(Vec<u8>, usize)>, YmodemError> {
+        let offset = self.pos.load(Ordering::Relaxed);
+        let remaining = self.source.total_len() - offset;
+        if remaining == 0 {
+            return Ok(None);
         }
-        Ok(())
+        let block_size = if remaining > PACKET_SIZE {
+            self.block_size.len()
+        } else {
+            PACKET_SIZE
+        };
+        let to_read = remaining.min(block_size);
+        let chunk = self.source.read_chunk(offset, to_read)?;
+        self.pos.store(offset + to_read, Ordering::Relaxed);
+        Ok(Some((chunk, block_size)))
     }
-    #[cfg(feature = "async")]
-    async fn send_packet_async(
-        &self,
-        port: &mut serial2_tokio::SerialPort,
-        packet: &[u8],
-    ) -> Result<(), YmodemError> {
-        port.write_all(packet).await.unwrap();
-        while let Err(e) = rcv::r#async::wait_for_ack(port).await {
+    fn send_packet<T: Read + Write>(&self, port: &mut T, packet: &[u8]) -> Result<(), YmodemError> {
+        check_cancelled(port, &self.cancel)?;
+        port.write_all(packet)?;
+        while let Err(e) = rcv::wait_for_ack(&mut *port, &self.cancel) {
             if e == YmodemError::RequestReSend {
-                port.write_all(packet).await.unwrap();
+                check_cancelled(port, &self.cancel)?;
+                port.write_all(packet)?;
             } else {
                 return Err(e);
             }
         }
         Ok(())
     }
-}
-impl<'a> YmodemSend for YmodemSender<'a> {
-    fn send(&self, port: &mut serial2::SerialPort) -> Result<(), YmodemError> {
-        let mut response = [0; 1];
-        loop {
-            port.read_exact(&mut response).unwrap();
-            if response[0] == YmodemControlCode::C as u8 {
-                break;
-            }
-        }
+    /// Sends this file's header and data blocks, ending with `Eot`.
+    ///
+    /// Leaves the receiver waiting to be told what comes next: another file's
+    /// header (batch transfers) or the zero-length header block that closes
+    /// the session. Does not itself wait for the session-opening `C`/`G`.
+    fn send_one<T: Read + Write>(&self, port: &mut T, mode: HandshakeMode) -> Result<(), YmodemError> {
         let file_header = self.create_file_header();
         self.send_packet(port, &file_header)?;
-        if rcv::wait_msg(port) != YmodemControlCode::C as u8 {
+        if rcv::wait_msg(port, &self.cancel)? != mode.control_byte() {
             return Err(YmodemError::InvalidResponse);
         }
-        for (block_number, chunk) in self.fdata.chunks(PACKET_SIZE).enumerate() {
-            let data_block = Self::create_data_block(chunk, (block_number + 1) as u8);
-            self.send_packet(port, &data_block)?;
+        let mut block_number: u8 = 1;
+        while let Some((chunk, block_size)) = self.next_block()? {
+            let data_block = create_data_block(&chunk, block_number, block_size);
+            match mode {
+                HandshakeMode::Crc => self.send_packet(port, &data_block)?,
+                HandshakeMode::Streaming => send_block_streaming(port, &data_block, &self.cancel)?,
+            }
+            block_number = block_number.wrapping_add(1);
         }
         // EOTの送信
+        //
+        // Even in YMODEM-G mode the receiver still ACKs EOT and re-issues its
+        // handshake byte before the next header (only data blocks go unacked),
+        // so both modes read back the same two-byte response here.
         self.send_packet(port, &[YmodemControlCode::Eot as u8])?;
-        if rcv::wait_msg(port) != YmodemControlCode::C as u8 {
+        if rcv::wait_msg(port, &self.cancel)? != mode.control_byte() {
             return Err(YmodemError::InvalidResponse);
         }
-        let data_block = Self::create_data_block(&[0; PACKET_SIZE], 0);
-        self.send_packet(port, &data_block)?;
-        // 最後のACKを待つ
         Ok(())
     }
 }
 #[cfg(feature = "async")]
-impl<'a> YmodemAsyncSend for YmodemSender<'a> {
-    async fn send(&self, port: &mut serial2_tokio::SerialPort) -> Result<(), YmodemError> {
-        let mut response = [0; 1];
-        loop {
-            port.read_exact(&mut response).await.unwrap();
-            if response[0] == YmodemControlCode::C as u8 {
-                break;
+impl<S: AsyncBlockSource> SenderCore<S> {
+    async fn next_block_async(&self) -> Result<Option<(Vec<u8>, usize)>, YmodemError> {
+        let offset = self.pos.load(Ordering::Relaxed);
+        let remaining = self.source.total_len() - offset;
+        if remaining == 0 {
+            return Ok(None);
+        }
+        let block_size = if remaining > PACKET_SIZE {
+            self.block_size.len()
+        } else {
+            PACKET_SIZE
+        };
+        let to_read = remaining.min(block_size);
+        let chunk = self.source.read_chunk_async(offset, to_read).await?;
+        self.pos.store(offset + to_read, Ordering::Relaxed);
+        Ok(Some((chunk, block_size)))
+    }
+    async fn send_packet_async<T: AsyncRead + AsyncWrite + Unpin + Send>(
+        &self,
+        port: &mut T,
+        packet: &[u8],
+    ) -> Result<(), YmodemError> {
+        check_cancelled_async(port, &self.cancel).await?;
+        port.write_all(packet).await?;
+        while let Err(e) = rcv::r#async::wait_for_ack(port, &self.cancel).await {
+            if e == YmodemError::RequestReSend {
+                check_cancelled_async(port, &self.cancel).await?;
+                port.write_all(packet).await?;
+            } else {
+                return Err(e);
             }
         }
+        Ok(())
+    }
+    async fn send_one_async<T: AsyncRead + AsyncWrite + Unpin + Send>(
+        &self,
+        port: &mut T,
+        mode: HandshakeMode,
+    ) -> Result<(), YmodemError> {
         let file_header = self.create_file_header();
         self.send_packet_async(port, &file_header).await?;
-        if rcv::r#async::wait_msg(port).await != YmodemControlCode::C as u8 {
+        if rcv::r#async::wait_msg(port, &self.cancel).await? != mode.control_byte() {
             return Err(YmodemError::InvalidResponse);
         }
-        for (block_number, chunk) in self.fdata.chunks(PACKET_SIZE).enumerate() {
-            let data_block = Self::create_data_block(chunk, (block_number + 1) as u8);
-            self.send_packet_async(port, &data_block).await?;
+        let mut block_number: u8 = 1;
+        while let Some((chunk, block_size)) = self.next_block_async().await? {
+            let data_block = create_data_block(&chunk, block_number, block_size);
+            match mode {
+                HandshakeMode::Crc => self.send_packet_async(port, &data_block).await?,
+                HandshakeMode::Streaming => {
+                    send_block_streaming_async(port, &data_block, &self.cancel).await?
+                }
+            }
+            block_number = block_number.wrapping_add(1);
         }
-        // EOTの送信
+        // EOTの送信 (see the sync `send_one` for why both modes read it back the same way)
         self.send_packet_async(port, &[YmodemControlCode::Eot as u8])
             .await?;
-        if rcv::r#async::wait_msg(port).await != YmodemControlCode::C as u8 {
+        if rcv::r#async::wait_msg(port, &self.cancel).await? != mode.control_byte() {
             return Err(YmodemError::InvalidResponse);
         }
-        let data_block = Self::create_data_block(&[0; PACKET_SIZE], 0);
-        self.send_packet_async(port, &data_block).await?;
-        // 最後のACKを待つ
         Ok(())
     }
 }
+/// Builds the `Soh`/`Stx` framing (block number, complement, CRC16) around a
+/// data chunk, padding a short tail to `block_size` with [`PADDING_BYTE`].
+/// Shared by every [`SenderCore`] instance and the terminator block.
+fn create_data_block(chunk: &[u8], block_number: u8, block_size: usize) -> Vec<u8> {
+    let control_code = if block_size == PACKET_SIZE_1K {
+        YmodemControlCode::Stx
+    } else {
+        YmodemControlCode::Soh
+    };
+    let mut block = vec![control_code as u8, block_number, !block_number];
+    let mut data = vec![PADDING_BYTE; block_size];
+    data[..chunk.len()].copy_from_slice(chunk);
+    block.extend_from_slice(&data);
+    // Append the CRC big-endian (high byte first)
+    let crc_value = crc16_ccitt(&data);
+    block.push((crc_value >> 8) as u8);
+    block.push((crc_value & 0xFF) as u8);
+    block
+}
+/// Emits the standard two-`Can` + backspace abort sequence and returns
+/// [`YmodemError::Cancelled`] if `token` has been cancelled.
+fn check_cancelled<T: Write>(port: &mut T, token: &CancelToken) -> Result<(), YmodemError> {
+    if token.is_cancelled() {
+        send_cancel_sequence(port)?;
+        return Err(YmodemError::Cancelled);
+    }
+    Ok(())
+}
+#[cfg(feature = "async")]
+async fn check_cancelled_async<T: AsyncWrite + Unpin>(
+    port: &mut T,
+    token: &CancelToken,
+) -> Result<(), YmodemError> {
+    if token.is_cancelled() {
+        send_cancel_sequence_async(port).await?;
+        return Err(YmodemError::Cancelled);
+    }
+    Ok(())
+}
+/// Writes the sequence a YMODEM sender uses to abort a transfer in progress:
+/// two `Can` bytes followed by a backspace.
+fn send_cancel_sequence<T: Write>(port: &mut T) -> Result<(), YmodemError> {
+    port.write_all(&[YmodemControlCode::Can as u8, YmodemControlCode::Can as u8, 0x08])?;
+    Ok(())
+}
+#[cfg(feature = "async")]
+async fn send_cancel_sequence_async<T: AsyncWrite + Unpin>(port: &mut T) -> Result<(), YmodemError> {
+    port.write_all(&[YmodemControlCode::Can as u8, YmodemControlCode::Can as u8, 0x08])
+        .await?;
+    Ok(())
+}
+/// Writes a YMODEM-G data block without waiting for a per-block ACK, only
+/// checking whether the caller's [`CancelToken`] fired or the receiver has
+/// already sent a `Can` to abort.
+fn send_block_streaming<T: Read + Write>(
+    port: &mut T,
+    block: &[u8],
+    token: &CancelToken,
+) -> Result<(), YmodemError> {
+    check_cancelled(port, token)?;
+    if poll_byte(port) == Some(YmodemControlCode::Can as u8) {
+        return Err(YmodemError::Cancelled);
+    }
+    port.write_all(block)?;
+    Ok(())
+}
+#[cfg(feature = "async")]
+async fn send_block_streaming_async<T: AsyncRead + AsyncWrite + Unpin + Send>(
+    port: &mut T,
+    block: &[u8],
+    token: &CancelToken,
+) -> Result<(), YmodemError> {
+    check_cancelled_async(port, token).await?;
+    if poll_byte_async(port).await == Some(YmodemControlCode::Can as u8) {
+        return Err(YmodemError::Cancelled);
+    }
+    port.write_all(block).await?;
+    Ok(())
+}
+/// Best-effort read of a single byte, used to poll for a `Can` abort signal
+/// while streaming YMODEM-G blocks. This is a plain `read`, so it blocks until
+/// a byte arrives or the transport's own read timeout elapses; a transport
+/// with no read timeout blocks here until the receiver sends something.
+/// Treats any I/O error (including that timeout) as "nothing available yet"
+/// rather than a fatal error.
+fn poll_byte<T: Read>(port: &mut T) -> Option<u8> {
+    let mut byte = [0u8; 1];
+    match port.read(&mut byte) {
+        Ok(1) => Some(byte[0]),
+        _ => None,
+    }
+}
+#[cfg(feature = "async")]
+async fn poll_byte_async<T: AsyncRead + Unpin>(port: &mut T) -> Option<u8> {
+    let mut byte = [0u8; 1];
+    match port.read(&mut byte).await {
+        Ok(1) => Some(byte[0]),
+        _ => None,
+    }
+}
+/// Reads a single byte, re-checking `token` between attempts instead of
+/// blocking forever on one `read`, so [`CancelToken::cancel`] can interrupt a
+/// peer that never responds. See the caveat on [`CancelToken`]'s docs: this
+/// still requires the transport to have a read timeout configured, the same
+/// assumption [`poll_byte`] already makes for YMODEM-G streaming.
+fn read_byte_cancellable<T: Read + Write>(
+    port: &mut T,
+    token: &CancelToken,
+) -> Result<u8, YmodemError> {
+    loop {
+        check_cancelled(port, token)?;
+        let mut byte = [0u8; 1];
+        match port.read(&mut byte) {
+            Ok(1) => return Ok(byte[0]),
+            Ok(_) => {}
+            Err(e) if matches!(e.kind(), std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut) => {}
+            Err(e) => return Err(e.into()),
+        }
+        std::thread::sleep(CANCEL_POLL_INTERVAL);
+    }
+}
+/// Async counterpart of [`read_byte_cancellable`]. Bounds the wait with a real
+/// `tokio::time::timeout` instead of relying on the transport to time out its
+/// own reads, so cancellation works regardless of how `T` is configured.
+#[cfg(feature = "async")]
+async fn read_byte_cancellable_async<T: AsyncRead + AsyncWrite + Unpin + Send>(
+    port: &mut T,
+    token: &CancelToken,
+) -> Result<u8, YmodemError> {
+    loop {
+        check_cancelled_async(port, token).await?;
+        let mut byte = [0u8; 1];
+        match tokio::time::timeout(CANCEL_POLL_INTERVAL, port.read(&mut byte)).await {
+            Ok(Ok(1)) => return Ok(byte[0]),
+            Ok(Ok(_)) => {}
+            Ok(Err(e)) => return Err(e.into()),
+            Err(_elapsed) => {}
+        }
+    }
+}
+/// Blocks until the receiver's session-opening `C` (CRC mode) or `G`
+/// (YMODEM-G streaming mode) byte arrives.
+fn await_session_start<T: Read + Write>(
+    port: &mut T,
+    token: &CancelToken,
+) -> Result<HandshakeMode, YmodemError> {
+    loop {
+        let byte = read_byte_cancellable(port, token)?;
+        if byte == YmodemControlCode::C as u8 {
+            return Ok(HandshakeMode::Crc);
+        } else if byte == YmodemControlCode::G as u8 {
+            return Ok(HandshakeMode::Streaming);
+        }
+    }
+}
+#[cfg(feature = "async")]
+async fn await_session_start_async<T: AsyncRead + AsyncWrite + Unpin + Send>(
+    port: &mut T,
+    token: &CancelToken,
+) -> Result<HandshakeMode, YmodemError> {
+    loop {
+        let byte = read_byte_cancellable_async(port, token).await?;
+        if byte == YmodemControlCode::C as u8 {
+            return Ok(HandshakeMode::Crc);
+        } else if byte == YmodemControlCode::G as u8 {
+            return Ok(HandshakeMode::Streaming);
+        }
+    }
+}
+/// Sends the zero-length header block that closes a YMODEM session, then
+/// waits for the ACK that confirms the receiver is done. Both handshake
+/// modes do the same here; in [`HandshakeMode::Streaming`] the preceding
+/// `Eot` has already been ACKed as well (see `SenderCore::send_one`).
+fn send_terminator<T: Read + Write>(
+    port: &mut T,
+    _mode: HandshakeMode,
+    token: &CancelToken,
+) -> Result<(), YmodemError> {
+    let mut terminator = SenderCore::new("", SliceSource(&[]));
+    terminator.cancel = token.clone();
+    let data_block = create_data_block(&[0; PACKET_SIZE], 0, PACKET_SIZE);
+    terminator.send_packet(port, &data_block)
+}
+#[cfg(feature = "async")]
+async fn send_terminator_async<T: AsyncRead + AsyncWrite + Unpin + Send>(
+    port: &mut T,
+    _mode: HandshakeMode,
+    token: &CancelToken,
+) -> Result<(), YmodemError> {
+    let mut terminator = SenderCore::new("", SliceSource(&[]));
+    terminator.cancel = token.clone();
+    let data_block = create_data_block(&[0; PACKET_SIZE], 0, PACKET_SIZE);
+    terminator.send_packet_async(port, &data_block).await
+}
+/// Sends a single in-memory payload over a YMODEM session.
+pub struct YmodemSender<'a> {
+    core: SenderCore<SliceSource<'a>>,
+}
+impl<'a> YmodemSender<'a> {
+    pub fn new(fname: &str, fdata: &'a [u8]) -> Self {
+        Self {
+            core: SenderCore::new(fname, SliceSource(fdata)),
+        }
+    }
+    /// Force the block size used for data packets (128-byte `Soh` or 1024-byte `Stx`).
+    ///
+    /// A final block of 128 bytes or fewer always falls back to a 128-byte `Soh` packet
+    /// regardless of this setting, since padding a near-empty tail to 1024 bytes wastes
+    /// bandwidth; a longer short tail is sent as one SUB-padded `Stx` packet. The file-info header is unaffected and always stays a `Soh` packet.
+    pub fn with_block_size(mut self, block_size: BlockSize) -> Self {
+        self.core.block_size = block_size;
+        self
+    }
+    /// Lets a transfer in progress be aborted cooperatively via `token.cancel()`.
+    pub fn with_cancel_token(mut self, token: CancelToken) -> Self {
+        self.core.cancel = token;
+        self
+    }
+}
+impl<'a> YmodemSend for YmodemSender<'a> {
+    fn send<T: Read + Write>(&self, port: &mut T) -> Result<(), YmodemError> {
+        let mode = await_session_start(port, &self.core.cancel)?;
+        self.core.send_one(port, mode)?;
+        // 最後のACKを待つ
+        send_terminator(port, mode, &self.core.cancel)
+    }
+}
+#[cfg(feature = "async")]
+impl<'a> YmodemAsyncSend for YmodemSender<'a> {
+    async fn send<T: AsyncRead + AsyncWrite + Unpin + Send>(
+        &self,
+        port: &mut T,
+    ) -> Result<(), YmodemError> {
+        let mode = await_session_start_async(port, &self.core.cancel).await?;
+        self.core.send_one_async(port, mode).await?;
+        // 最後のACKを待つ
+        send_terminator_async(port, mode, &self.core.cancel).await
+    }
+}
+/// Sends a batch of files back-to-back in a single YMODEM session.
+///
+/// Each file's header follows directly after the previous file's `Eot`, and
+/// the zero-length header block that closes the session is sent only once,
+/// after the last file.
+pub struct YmodemBatchSender<'a> {
+    files: Vec<YmodemSender<'a>>,
+    cancel: CancelToken,
+}
+impl<'a> YmodemBatchSender<'a> {
+    pub fn new(files: Vec<(&str, &'a [u8])>) -> Self {
+        Self {
+            files: files
+                .into_iter()
+                .map(|(fname, fdata)| YmodemSender::new(fname, fdata))
+                .collect(),
+            cancel: CancelToken::new(),
+        }
+    }
+    /// Force the block size used for data packets of every file in the batch.
+    pub fn with_block_size(mut self, block_size: BlockSize) -> Self {
+        for sender in &mut self.files {
+            sender.core.block_size = block_size;
+        }
+        self
+    }
+    /// Lets the whole batch be aborted cooperatively via `token.cancel()`.
+    pub fn with_cancel_token(mut self, token: CancelToken) -> Self {
+        for sender in &mut self.files {
+            sender.core.cancel = token.clone();
+        }
+        self.cancel = token;
+        self
+    }
+}
+impl<'a> YmodemSend for YmodemBatchSender<'a> {
+    fn send<T: Read + Write>(&self, port: &mut T) -> Result<(), YmodemError> {
+        let mode = await_session_start(port, &self.cancel)?;
+        for sender in &self.files {
+            sender.core.send_one(port, mode)?;
+        }
+        send_terminator(port, mode, &self.cancel)
+    }
+}
+#[cfg(feature = "async")]
+impl<'a> YmodemAsyncSend for YmodemBatchSender<'a> {
+    async fn send<T: AsyncRead + AsyncWrite + Unpin + Send>(
+        &self,
+        port: &mut T,
+    ) -> Result<(), YmodemError> {
+        let mode = await_session_start_async(port, &self.cancel).await?;
+        for sender in &self.files {
+            sender.core.send_one_async(port, mode).await?;
+        }
+        send_terminator_async(port, mode, &self.cancel).await
+    }
+}
+#[cfg(feature = "mmap")]
+impl<'a> YmodemSender<'a> {
+    /// Builds a sender backed directly by a memory-mapped file, avoiding a
+    /// heap copy of the payload.
+    pub fn from_mmap(fname: &str, mmap: &'a memmap2::Mmap) -> Self {
+        Self::new(fname, &mmap[..])
+    }
+}
+/// Sends a file read incrementally from `R`, one block at a time, instead of
+/// requiring the whole payload to be buffered in memory up front.
+///
+/// `len` is the total number of bytes `reader` will yield; it is needed up
+/// front for the file-info header's size field, so it cannot be discovered
+/// by draining the reader first.
+pub struct YmodemStreamSender<R> {
+    core: SenderCore<ReaderSource<R>>,
+}
+impl<R> YmodemStreamSender<R> {
+    /// Not bounded by `Read`/`AsyncRead` here: construction and the builder
+    /// methods below don't touch `reader`, so an async-only `R` (e.g.
+    /// `tokio::net::TcpStream`) can be built and later sent with
+    /// [`YmodemAsyncSend::send`] even though it can't satisfy [`YmodemSend`].
+    pub fn new(fname: &str, reader: R, len: usize) -> Self {
+        Self {
+            core: SenderCore::new(
+                fname,
+                ReaderSource {
+                    #[cfg(not(feature = "async"))]
+                    reader: RefCell::new(reader),
+                    #[cfg(feature = "async")]
+                    reader: AsyncMutex::new(reader),
+                    len,
+                },
+            ),
+        }
+    }
+    /// Force the block size used for data packets (128-byte `Soh` or 1024-byte `Stx`).
+    pub fn with_block_size(mut self, block_size: BlockSize) -> Self {
+        self.core.block_size = block_size;
+        self
+    }
+    /// Lets a transfer in progress be aborted cooperatively via `token.cancel()`.
+    pub fn with_cancel_token(mut self, token: CancelToken) -> Self {
+        self.core.cancel = token;
+        self
+    }
+}
+impl<R: Read> YmodemSend for YmodemStreamSender<R> {
+    fn send<T: Read + Write>(&self, port: &mut T) -> Result<(), YmodemError> {
+        let mode = await_session_start(port, &self.core.cancel)?;
+        self.core.send_one(port, mode)?;
+        send_terminator(port, mode, &self.core.cancel)
+    }
+}
+#[cfg(feature = "async")]
+impl<R: AsyncRead + Unpin + Send> YmodemAsyncSend for YmodemStreamSender<R> {
+    async fn send<T: AsyncRead + AsyncWrite + Unpin + Send>(
+        &self,
+        port: &mut T,
+    ) -> Result<(), YmodemError> {
+        let mode = await_session_start_async(port, &self.core.cancel).await?;
+        self.core.send_one_async(port, mode).await?;
+        send_terminator_async(port, mode, &self.core.cancel).await
+    }
+}
 
 fn crc16_ccitt(data: &[u8]) -> u16 {
     let mut crc = 0u16;
@@ -187,3 +783,91 @@ fn crc16_ccitt(data: &[u8]) -> u16 {
     }
     crc
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    /// One end of an in-memory, full-duplex byte pipe: the generic `Read +
+    /// Write` transport `send`/`receive_file` are parameterized over,
+    /// standing in for a real serial port or socket.
+    struct LoopbackPort {
+        tx: mpsc::Sender<u8>,
+        rx: mpsc::Receiver<u8>,
+    }
+    fn loopback_pair() -> (LoopbackPort, LoopbackPort) {
+        let (tx_a, rx_a) = mpsc::channel();
+        let (tx_b, rx_b) = mpsc::channel();
+        (
+            LoopbackPort { tx: tx_a, rx: rx_b },
+            LoopbackPort { tx: tx_b, rx: rx_a },
+        )
+    }
+    impl Read for LoopbackPort {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            if buf.is_empty() {
+                return Ok(0);
+            }
+            match self.rx.recv() {
+                Ok(byte) => {
+                    buf[0] = byte;
+                    Ok(1)
+                }
+                Err(_) => Ok(0),
+            }
+        }
+    }
+    impl Write for LoopbackPort {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            for &byte in buf {
+                self.tx
+                    .send(byte)
+                    .map_err(|_| std::io::Error::from(std::io::ErrorKind::BrokenPipe))?;
+            }
+            Ok(buf.len())
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn sender_and_receiver_round_trip_over_a_generic_transport() {
+        let (mut sender_port, mut receiver_port) = loopback_pair();
+        let payload = b"hello ymodem".to_vec();
+        let send_thread = std::thread::spawn(move || {
+            let sender = YmodemSender::new("greeting.txt", &payload);
+            sender.send(&mut sender_port).unwrap();
+        });
+        let receiver = YmodemReceiver::new();
+        let mut received = Vec::new();
+        let filename = receiver
+            .receive_file(&mut receiver_port, &mut received)
+            .unwrap();
+        assert_eq!(filename.as_deref(), Some("greeting.txt"));
+        assert_eq!(received, b"hello ymodem");
+        // Drains the zero-length header block that closes the session;
+        // otherwise `send_thread` blocks forever inside `send_terminator`.
+        let mut discarded = Vec::new();
+        let terminator = receiver
+            .receive_file(&mut receiver_port, &mut discarded)
+            .unwrap();
+        assert_eq!(terminator, None);
+        send_thread.join().unwrap();
+    }
+
+    #[test]
+    fn one_k_block_size_still_falls_back_to_a_short_soh_tail() {
+        let payload = [0x41u8; PACKET_SIZE_1K + 100];
+        let mut core = SenderCore::new("firmware.bin", SliceSource(&payload));
+        core.block_size = BlockSize::OneK;
+        let (first, first_block_size) = core.next_block().unwrap().unwrap();
+        assert_eq!(first.len(), PACKET_SIZE_1K);
+        assert_eq!(first_block_size, PACKET_SIZE_1K);
+        let (tail, tail_block_size) = core.next_block().unwrap().unwrap();
+        assert_eq!(tail.len(), 100);
+        assert_eq!(tail_block_size, PACKET_SIZE);
+        assert!(core.next_block().unwrap().is_none());
+    }
+}
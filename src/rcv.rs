@@ -0,0 +1,40 @@
+use crate::{CancelToken, YmodemControlCode, YmodemError};
+use std::io::{Read, Write};
+
+pub fn wait_for_ack<T: Read + Write>(port: &mut T, token: &CancelToken) -> Result<(), YmodemError> {
+    match crate::read_byte_cancellable(port, token)? {
+        x if x == YmodemControlCode::Ack as u8 => Ok(()),
+        x if x == YmodemControlCode::Nak as u8 => Err(YmodemError::RequestReSend),
+        x if x == YmodemControlCode::Can as u8 => Err(YmodemError::Cancelled),
+        _ => Err(YmodemError::InvalidResponse),
+    }
+}
+
+pub fn wait_msg<T: Read + Write>(port: &mut T, token: &CancelToken) -> Result<u8, YmodemError> {
+    crate::read_byte_cancellable(port, token)
+}
+
+#[cfg(feature = "async")]
+pub mod r#async {
+    use crate::{CancelToken, YmodemControlCode, YmodemError};
+    use tokio::io::{AsyncRead, AsyncWrite};
+
+    pub async fn wait_for_ack<T: AsyncRead + AsyncWrite + Unpin + Send>(
+        port: &mut T,
+        token: &CancelToken,
+    ) -> Result<(), YmodemError> {
+        match crate::read_byte_cancellable_async(port, token).await? {
+            x if x == YmodemControlCode::Ack as u8 => Ok(()),
+            x if x == YmodemControlCode::Nak as u8 => Err(YmodemError::RequestReSend),
+            x if x == YmodemControlCode::Can as u8 => Err(YmodemError::Cancelled),
+            _ => Err(YmodemError::InvalidResponse),
+        }
+    }
+
+    pub async fn wait_msg<T: AsyncRead + AsyncWrite + Unpin + Send>(
+        port: &mut T,
+        token: &CancelToken,
+    ) -> Result<u8, YmodemError> {
+        crate::read_byte_cancellable_async(port, token).await
+    }
+}
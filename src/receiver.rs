@@ -0,0 +1,377 @@
+use crate::{crc16_ccitt, YmodemControlCode, YmodemError, PACKET_SIZE, PACKET_SIZE_1K};
+use bytes::{Buf, BytesMut};
+use std::io::{Read, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(feature = "async")]
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+#[cfg(feature = "async")]
+use tokio_util::codec::Decoder;
+
+/// A single decoded YMODEM frame, as produced by [`YmodemCodec`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum YmodemFrame {
+    /// A `Soh`/`Stx` data block, already sequence- and CRC-verified.
+    Block { block_number: u8, payload: Vec<u8> },
+    /// The `Eot` control byte that ends a file's data stream.
+    Eot,
+}
+
+/// Decodes the wire framing of YMODEM `Soh`/`Stx`/`Eot` packets out of a byte
+/// stream.
+///
+/// A `Soh` frame is fixed at 133 bytes (`1 + 2 + 128 + 2`), a `Stx` frame at
+/// 1029 bytes (`1 + 2 + 1024 + 2`), and `Eot` is a single byte; `decode_frame`
+/// returns `Ok(None)` until the full fixed length for the leading control
+/// byte has been buffered. The block-number complement and the trailing
+/// CRC16 are verified before a frame is yielded.
+///
+/// The framing logic lives in this inherent method rather than directly in
+/// the `tokio_util::codec::Decoder` impl below so it stays usable without
+/// the `async` feature: `tokio_util` isn't a dependency sync-only callers
+/// should have to pull in just to receive over a plain `Read + Write`.
+#[derive(Debug, Default)]
+pub struct YmodemCodec;
+
+impl YmodemCodec {
+    fn decode_frame(&mut self, src: &mut BytesMut) -> Result<Option<YmodemFrame>, YmodemError> {
+        if src.is_empty() {
+            return Ok(None);
+        }
+        let control = src[0];
+        if control == YmodemControlCode::Eot as u8 {
+            src.advance(1);
+            return Ok(Some(YmodemFrame::Eot));
+        }
+        let block_size = if control == YmodemControlCode::Soh as u8 {
+            PACKET_SIZE
+        } else if control == YmodemControlCode::Stx as u8 {
+            PACKET_SIZE_1K
+        } else {
+            // Not a control byte we understand; drop it so the caller can
+            // resync instead of getting stuck rereading it forever.
+            src.advance(1);
+            return Err(YmodemError::InvalidResponse);
+        };
+        let frame_len = 3 + block_size + 2;
+        if src.len() < frame_len {
+            return Ok(None);
+        }
+        let frame = src.split_to(frame_len);
+        let block_number = frame[1];
+        if frame[2] != !block_number {
+            return Err(YmodemError::InvalidResponse);
+        }
+        let payload = &frame[3..3 + block_size];
+        let crc = ((frame[3 + block_size] as u16) << 8) | frame[3 + block_size + 1] as u16;
+        if crc16_ccitt(payload) != crc {
+            return Err(YmodemError::RequestReSend);
+        }
+        Ok(Some(YmodemFrame::Block {
+            block_number,
+            payload: payload.to_vec(),
+        }))
+    }
+}
+
+#[cfg(feature = "async")]
+impl Decoder for YmodemCodec {
+    type Item = YmodemFrame;
+    type Error = YmodemError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        self.decode_frame(src)
+    }
+}
+
+/// Parsed contents of a YMODEM file-info header block.
+struct FileInfo {
+    filename: String,
+    len: usize,
+}
+
+/// Parses a file-info header's 128-byte payload, or `None` for the
+/// all-zero header that closes a batch.
+fn parse_file_info(payload: &[u8]) -> Option<FileInfo> {
+    if payload.iter().all(|&b| b == 0) {
+        return None;
+    }
+    let mut fields = payload.splitn(2, |&b| b == 0);
+    let filename = fields.next()?;
+    let rest = fields.next().unwrap_or(&[]);
+    let len_field = rest.split(|&b| b == 0 || b == 0x20).next().unwrap_or(&[]);
+    Some(FileInfo {
+        filename: String::from_utf8_lossy(filename).into_owned(),
+        len: std::str::from_utf8(len_field).ok()?.parse().ok()?,
+    })
+}
+
+/// Receives files sent over a YMODEM session, reassembling each into a
+/// caller-supplied writer.
+///
+/// Drives the session by emitting `C`, decoding incoming packets with
+/// [`YmodemCodec`], and ACKing/NAKing according to the sequence and CRC
+/// checks the codec already performs.
+///
+/// A session is one or more calls to `receive_file`/`receive_file_async`,
+/// ending with the call that reads the zero-length header block and returns
+/// `Ok(None)`. Only the very first call sends the opening `C`: acking `Eot`
+/// re-emits it already (mirroring the file-info header's own re-emitted
+/// `C`), since the sender waits for it before the next file's header or the
+/// closing terminator, so a later call must not send a redundant one.
+///
+/// `session_started` is an `AtomicBool` rather than a `Cell` so
+/// `YmodemReceiver` stays `Sync`, matching [`crate::SenderCore`]'s `pos`
+/// field: `receive_file_async` takes `&self` across an `.await`, and a
+/// caller spawning it onto an executor needs the resulting future to be
+/// `Send`, which requires `Self: Sync`.
+#[derive(Debug, Default)]
+pub struct YmodemReceiver {
+    session_started: AtomicBool,
+}
+
+impl YmodemReceiver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Receives one file into `writer`, returning its declared filename, or
+    /// `Ok(None)` if the sender closed the session instead (the zero-length
+    /// header block).
+    pub fn receive_file<T: Read + Write, W: Write>(
+        &self,
+        port: &mut T,
+        writer: &mut W,
+    ) -> Result<Option<String>, YmodemError> {
+        let mut codec = YmodemCodec;
+        let mut buf = BytesMut::new();
+        if !self.session_started.swap(true, Ordering::Relaxed) {
+            port.write_all(&[YmodemControlCode::C as u8])?;
+        }
+        let header = loop {
+            match Self::next_frame(port, &mut codec, &mut buf) {
+                Ok(YmodemFrame::Block {
+                    block_number: 0,
+                    payload,
+                }) => break payload,
+                Ok(_) | Err(YmodemError::RequestReSend) => {
+                    port.write_all(&[YmodemControlCode::Nak as u8])?;
+                }
+                Err(e) => return Err(e),
+            }
+        };
+        port.write_all(&[YmodemControlCode::Ack as u8])?;
+        let Some(info) = parse_file_info(&header) else {
+            return Ok(None);
+        };
+        port.write_all(&[YmodemControlCode::C as u8])?;
+        let mut received = 0usize;
+        let mut expected_block: u8 = 1;
+        loop {
+            match Self::next_frame(port, &mut codec, &mut buf) {
+                Ok(YmodemFrame::Eot) => {
+                    port.write_all(&[YmodemControlCode::Ack as u8])?;
+                    // The sender blocks after Eot for the same handshake byte
+                    // it waited for before this file's header, whether that's
+                    // the next file in a batch or the closing terminator.
+                    port.write_all(&[YmodemControlCode::C as u8])?;
+                    break;
+                }
+                Ok(YmodemFrame::Block {
+                    block_number,
+                    payload,
+                }) => {
+                    if block_number != expected_block {
+                        port.write_all(&[YmodemControlCode::Nak as u8])?;
+                        continue;
+                    }
+                    let take = payload.len().min(info.len - received);
+                    writer.write_all(&payload[..take])?;
+                    received += take;
+                    expected_block = expected_block.wrapping_add(1);
+                    port.write_all(&[YmodemControlCode::Ack as u8])?;
+                }
+                Err(YmodemError::RequestReSend) => {
+                    port.write_all(&[YmodemControlCode::Nak as u8])?;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(Some(info.filename))
+    }
+
+    fn next_frame<T: Read>(
+        port: &mut T,
+        codec: &mut YmodemCodec,
+        buf: &mut BytesMut,
+    ) -> Result<YmodemFrame, YmodemError> {
+        loop {
+            if let Some(frame) = codec.decode_frame(buf)? {
+                return Ok(frame);
+            }
+            let mut chunk = [0u8; 256];
+            let n = port.read(&mut chunk).map_err(|_| YmodemError::Timeout)?;
+            if n == 0 {
+                return Err(YmodemError::Timeout);
+            }
+            buf.extend_from_slice(&chunk[..n]);
+        }
+    }
+}
+#[cfg(feature = "async")]
+impl YmodemReceiver {
+    /// Async counterpart of [`YmodemReceiver::receive_file`].
+    pub async fn receive_file_async<T: AsyncRead + AsyncWrite + Unpin + Send, W: Write>(
+        &self,
+        port: &mut T,
+        writer: &mut W,
+    ) -> Result<Option<String>, YmodemError> {
+        let mut codec = YmodemCodec;
+        let mut buf = BytesMut::new();
+        if !self.session_started.swap(true, Ordering::Relaxed) {
+            port.write_all(&[YmodemControlCode::C as u8]).await?;
+        }
+        let header = loop {
+            match Self::next_frame_async(port, &mut codec, &mut buf).await {
+                Ok(YmodemFrame::Block {
+                    block_number: 0,
+                    payload,
+                }) => break payload,
+                Ok(_) | Err(YmodemError::RequestReSend) => {
+                    port.write_all(&[YmodemControlCode::Nak as u8]).await?;
+                }
+                Err(e) => return Err(e),
+            }
+        };
+        port.write_all(&[YmodemControlCode::Ack as u8]).await?;
+        let Some(info) = parse_file_info(&header) else {
+            return Ok(None);
+        };
+        port.write_all(&[YmodemControlCode::C as u8]).await?;
+        let mut received = 0usize;
+        let mut expected_block: u8 = 1;
+        loop {
+            match Self::next_frame_async(port, &mut codec, &mut buf).await {
+                Ok(YmodemFrame::Eot) => {
+                    port.write_all(&[YmodemControlCode::Ack as u8]).await?;
+                    port.write_all(&[YmodemControlCode::C as u8]).await?;
+                    break;
+                }
+                Ok(YmodemFrame::Block {
+                    block_number,
+                    payload,
+                }) => {
+                    if block_number != expected_block {
+                        port.write_all(&[YmodemControlCode::Nak as u8]).await?;
+                        continue;
+                    }
+                    let take = payload.len().min(info.len - received);
+                    writer.write_all(&payload[..take])?;
+                    received += take;
+                    expected_block = expected_block.wrapping_add(1);
+                    port.write_all(&[YmodemControlCode::Ack as u8]).await?;
+                }
+                Err(YmodemError::RequestReSend) => {
+                    port.write_all(&[YmodemControlCode::Nak as u8]).await?;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(Some(info.filename))
+    }
+
+    async fn next_frame_async<T: AsyncRead + Unpin + Send>(
+        port: &mut T,
+        codec: &mut YmodemCodec,
+        buf: &mut BytesMut,
+    ) -> Result<YmodemFrame, YmodemError> {
+        loop {
+            if let Some(frame) = codec.decode_frame(buf)? {
+                return Ok(frame);
+            }
+            let mut chunk = [0u8; 256];
+            let n = port.read(&mut chunk).await.map_err(|_| YmodemError::Timeout)?;
+            if n == 0 {
+                return Err(YmodemError::Timeout);
+            }
+            buf.extend_from_slice(&chunk[..n]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn soh_frame(block_number: u8, payload: &[u8; PACKET_SIZE]) -> BytesMut {
+        let mut frame = BytesMut::new();
+        frame.extend_from_slice(&[YmodemControlCode::Soh as u8, block_number, !block_number]);
+        frame.extend_from_slice(payload);
+        let crc = crc16_ccitt(payload);
+        frame.extend_from_slice(&[(crc >> 8) as u8, (crc & 0xFF) as u8]);
+        frame
+    }
+
+    #[test]
+    fn decode_returns_none_until_full_frame_buffered() {
+        let mut codec = YmodemCodec;
+        let full = soh_frame(1, &[0x41; PACKET_SIZE]);
+        let mut buf = BytesMut::from(&full[..full.len() - 1]);
+        assert_eq!(codec.decode_frame(&mut buf).unwrap(), None);
+    }
+
+    #[test]
+    fn decode_yields_soh_block() {
+        let mut codec = YmodemCodec;
+        let mut buf = soh_frame(1, &[0x41; PACKET_SIZE]);
+        let frame = codec.decode_frame(&mut buf).unwrap().unwrap();
+        assert_eq!(
+            frame,
+            YmodemFrame::Block {
+                block_number: 1,
+                payload: vec![0x41; PACKET_SIZE],
+            }
+        );
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn decode_yields_eot() {
+        let mut codec = YmodemCodec;
+        let mut buf = BytesMut::from(&[YmodemControlCode::Eot as u8][..]);
+        assert_eq!(codec.decode_frame(&mut buf).unwrap(), Some(YmodemFrame::Eot));
+    }
+
+    #[test]
+    fn decode_rejects_bad_block_complement() {
+        let mut codec = YmodemCodec;
+        let mut frame = soh_frame(1, &[0x41; PACKET_SIZE]);
+        frame[2] = 0; // corrupt the complement byte
+        assert_eq!(codec.decode_frame(&mut frame), Err(YmodemError::InvalidResponse));
+    }
+
+    #[test]
+    fn decode_requests_resend_on_crc_mismatch() {
+        let mut codec = YmodemCodec;
+        let mut frame = soh_frame(1, &[0x41; PACKET_SIZE]);
+        let last = frame.len() - 1;
+        frame[last] ^= 0xFF; // corrupt the trailing CRC
+        assert_eq!(codec.decode_frame(&mut frame), Err(YmodemError::RequestReSend));
+    }
+
+    #[test]
+    fn parse_file_info_reads_name_and_length() {
+        let mut payload = [0u8; PACKET_SIZE];
+        payload[..b"firmware.bin".len()].copy_from_slice(b"firmware.bin");
+        payload[b"firmware.bin".len() + 1..b"firmware.bin".len() + 1 + b"4096".len()]
+            .copy_from_slice(b"4096");
+        let info = parse_file_info(&payload).unwrap();
+        assert_eq!(info.filename, "firmware.bin");
+        assert_eq!(info.len, 4096);
+    }
+
+    #[test]
+    fn parse_file_info_returns_none_for_all_zero_header() {
+        let payload = [0u8; PACKET_SIZE];
+        assert!(parse_file_info(&payload).is_none());
+    }
+}